@@ -0,0 +1,86 @@
+use grep_matcher::{Match, Matcher, NoError};
+
+/// Iterator over all non-overlapping matches of a needle in a haystack,
+/// built on top of a matcher's forward `find_at`.
+#[derive(Debug)]
+pub struct FindIter<'m, 'h, M> {
+    matcher: &'m M,
+    haystack: &'h [u8],
+    at: usize,
+}
+
+impl<'m, 'h, M: Matcher<Error = NoError>> FindIter<'m, 'h, M> {
+    pub fn new(matcher: &'m M, haystack: &'h [u8]) -> Self {
+        FindIter { matcher, haystack, at: 0 }
+    }
+}
+
+impl<'m, 'h, M: Matcher<Error = NoError>> Iterator for FindIter<'m, 'h, M> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Match> {
+        if self.at > self.haystack.len() {
+            return None;
+        }
+
+        let m = self.matcher.find_at(self.haystack, self.at).unwrap()?;
+
+        // Guard against a zero-width match making no progress, which would
+        // otherwise loop forever.
+        self.at = if m.end() > self.at { m.end() } else { self.at + 1 };
+
+        Some(m)
+    }
+}
+
+/// Yield all non-overlapping matches of `matcher` in `haystack`, in order.
+pub fn find_iter<'m, 'h, M: Matcher<Error = NoError>>(
+    matcher: &'m M,
+    haystack: &'h [u8],
+) -> FindIter<'m, 'h, M> {
+    FindIter::new(matcher, haystack)
+}
+
+/// Return the rightmost match of `matcher` in `haystack` ending at or
+/// before `end`, by scanning `haystack[..end]` forward with `find_at` and
+/// keeping the last match found.
+///
+/// Shared by matchers whose `Matcher` impl (an external trait) can't carry
+/// a default `rfind_at` method of its own; each one exposes an inherent
+/// `rfind_at` that just forwards here.
+pub fn rfind_at<M: Matcher<Error = NoError>>(
+    matcher: &M,
+    haystack: &[u8],
+    end: usize,
+) -> Result<Option<Match>, NoError> {
+    let haystack = &haystack[..end.min(haystack.len())];
+    let mut result = None;
+    let mut at = 0;
+
+    while let Some(m) = matcher.find_at(haystack, at)? {
+        result = Some(m);
+        at = m.start() + 1;
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::iter::find_iter;
+    use crate::NaiveMatcher;
+
+    #[test]
+    fn find_iter_collects_all_matches() {
+        let haystack = b"ababab";
+        let needle = Arc::new(b"ab".to_vec());
+        let matcher = NaiveMatcher::new(&needle);
+
+        let starts: Vec<usize> =
+            find_iter(&matcher, haystack).map(|m| m.start()).collect();
+
+        assert_eq!(starts, vec![0, 2, 4]);
+    }
+}
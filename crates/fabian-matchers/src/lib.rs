@@ -6,18 +6,35 @@ use std::sync::Arc;
 
 use grep_matcher::{Match, Matcher, NoCaptures, NoError};
 use memchr_matcher::MemchrMatcher;
+use multi_matcher::MultiMatcher;
 use naive_matcher::NaiveMatcher;
+use prefiltered_matcher::PrefilteredMatcher;
 use rabin_karp_matcher::RabinKarpMatcher;
+use shift_or_matcher::ShiftOrMatcher;
+use two_way_matcher::TwoWayMatcher;
+
+pub use pattern::Pattern;
 
 mod rabin_karp_matcher;
 mod naive_matcher;
 mod memchr_matcher;
+mod two_way_matcher;
+mod prefilter;
+mod prefiltered_matcher;
+mod shift_or_matcher;
+mod pattern;
+mod multi_matcher;
+pub mod iter;
 
 #[derive(Debug, Clone)]
 enum InnerMatcher {
     Naive(NaiveMatcher),
     RabinKarp(RabinKarpMatcher),
     Memchr(MemchrMatcher),
+    TwoWay(TwoWayMatcher),
+    Prefiltered(PrefilteredMatcher),
+    ShiftOr(ShiftOrMatcher),
+    Multi(MultiMatcher),
 }
 
 #[derive(Debug, Clone)]
@@ -37,6 +54,50 @@ impl FabianMatcher {
     pub fn memchr(needle: &Arc<Vec<u8>>) -> Self {
         FabianMatcher { inner: InnerMatcher::Memchr(MemchrMatcher::new(needle)) }
     }
+
+    /// Create a matcher that guarantees worst-case O(m+n) matching, no
+    /// matter the needle or haystack, via the Two-Way algorithm.
+    pub fn two_way(needle: &Arc<Vec<u8>>) -> Self {
+        FabianMatcher { inner: InnerMatcher::TwoWay(TwoWayMatcher::new(needle)) }
+    }
+
+    /// Create a matcher that uses a rare-byte prefilter to skip over
+    /// haystack regions that cannot contain a match before verifying.
+    pub fn with_prefilter(needle: &Arc<Vec<u8>>) -> Self {
+        FabianMatcher { inner: InnerMatcher::Prefiltered(PrefilteredMatcher::new(needle)) }
+    }
+
+    /// Create a Shift-Or (bitap) matcher for exact matching of short
+    /// needles (at most 64 bytes).
+    pub fn shift_or(needle: &Arc<Vec<u8>>) -> Self {
+        FabianMatcher { inner: InnerMatcher::ShiftOr(ShiftOrMatcher::exact(needle)) }
+    }
+
+    /// Create a Shift-Or matcher that tolerates up to `k` mismatches
+    /// (Hamming distance), useful for approximate log search.
+    pub fn fuzzy(needle: &Arc<Vec<u8>>, k: usize) -> Self {
+        FabianMatcher { inner: InnerMatcher::ShiftOr(ShiftOrMatcher::fuzzy(needle, k)) }
+    }
+
+    /// Create a matcher that searches for several alternative needles at
+    /// once and reports the leftmost match across all of them, e.g.
+    /// `rg`-style alternation (`foo|bar|baz` as literals).
+    pub fn multi<P: Pattern>(patterns: P) -> Self {
+        FabianMatcher { inner: InnerMatcher::Multi(MultiMatcher::new(patterns)) }
+    }
+
+    /// Return the rightmost match ending at or before `end`.
+    pub fn rfind_at(&self, haystack: &[u8], end: usize) -> Result<Option<Match>, NoError> {
+        match &self.inner {
+            InnerMatcher::RabinKarp(matcher) => matcher.rfind_at(haystack, end),
+            InnerMatcher::Naive(matcher) => matcher.rfind_at(haystack, end),
+            InnerMatcher::Memchr(matcher) => matcher.rfind_at(haystack, end),
+            InnerMatcher::TwoWay(matcher) => matcher.rfind_at(haystack, end),
+            InnerMatcher::Prefiltered(matcher) => matcher.rfind_at(haystack, end),
+            InnerMatcher::ShiftOr(matcher) => matcher.rfind_at(haystack, end),
+            InnerMatcher::Multi(matcher) => matcher.rfind_at(haystack, end),
+        }
+    }
 }
 
 impl Matcher for FabianMatcher {
@@ -48,6 +109,10 @@ impl Matcher for FabianMatcher {
             InnerMatcher::RabinKarp(matcher) => matcher.find_at(haystack, at),
             InnerMatcher::Naive(matcher) => matcher.find_at(haystack, at),
             InnerMatcher::Memchr(matcher) => matcher.find_at(haystack, at),
+            InnerMatcher::TwoWay(matcher) => matcher.find_at(haystack, at),
+            InnerMatcher::Prefiltered(matcher) => matcher.find_at(haystack, at),
+            InnerMatcher::ShiftOr(matcher) => matcher.find_at(haystack, at),
+            InnerMatcher::Multi(matcher) => matcher.find_at(haystack, at),
         }
     }
 
@@ -56,6 +121,10 @@ impl Matcher for FabianMatcher {
             InnerMatcher::RabinKarp(matcher) => matcher.new_captures(),
             InnerMatcher::Naive(matcher) => matcher.new_captures(),
             InnerMatcher::Memchr(matcher) => matcher.new_captures(),
+            InnerMatcher::TwoWay(matcher) => matcher.new_captures(),
+            InnerMatcher::Prefiltered(matcher) => matcher.new_captures(),
+            InnerMatcher::ShiftOr(matcher) => matcher.new_captures(),
+            InnerMatcher::Multi(matcher) => matcher.new_captures(),
         }
     }
 }
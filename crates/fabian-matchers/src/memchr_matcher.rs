@@ -4,6 +4,8 @@ use memchr::arch::all::rabinkarp::Finder;
 
 use grep_matcher::{Match, Matcher, NoCaptures, NoError};
 
+use crate::iter;
+
 #[derive(Debug)]
 pub struct MemchrMatcher {
     needle: Arc<Vec<u8>>,
@@ -38,3 +40,31 @@ impl Matcher for MemchrMatcher {
         Ok(NoCaptures::new())
     }
 }
+
+impl MemchrMatcher {
+    /// Return the rightmost match ending at or before `end`.
+    pub fn rfind_at(&self, haystack: &[u8], end: usize) -> Result<Option<Match>, NoError> {
+        iter::rfind_at(self, haystack, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use grep_matcher::Matcher;
+
+    use crate::MemchrMatcher;
+
+    #[test]
+    fn rfind_at_returns_rightmost_match() {
+        let haystack = b"hello world hello";
+        let needle = Arc::new(b"hello".to_vec());
+        let matcher = MemchrMatcher::new(&needle);
+
+        let result = matcher.rfind_at(haystack, haystack.len());
+        let r#match = result.unwrap().expect("expected to find a match");
+
+        assert_eq!(r#match.start(), 12);
+    }
+}
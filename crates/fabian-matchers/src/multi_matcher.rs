@@ -0,0 +1,160 @@
+use std::sync::Arc;
+
+use grep_matcher::{Match, Matcher, NoCaptures, NoError};
+
+use crate::iter;
+use crate::pattern::Pattern;
+
+/// Searches for several needles at once and reports the leftmost match
+/// across all of them, so callers can run `rg`-style literal alternation
+/// (`foo|bar|baz`) in a single pass over the haystack.
+#[derive(Debug, Clone)]
+pub struct MultiMatcher {
+    needles: Vec<Arc<Vec<u8>>>,
+}
+
+impl MultiMatcher {
+    pub fn new<P: Pattern>(patterns: P) -> Self {
+        MultiMatcher { needles: patterns.into_patterns() }
+    }
+
+    /// Find the leftmost match across all patterns, returning the index of
+    /// the pattern that matched alongside the match itself. Ties (several
+    /// patterns starting at the same position) are broken by longest match.
+    pub fn find_at_with_index(
+        &self,
+        haystack: &[u8],
+        at: usize,
+    ) -> Result<Option<(usize, Match)>, NoError> {
+        let hay = &haystack[at..];
+        let first_bytes: Vec<u8> =
+            self.needles.iter().filter_map(|n| n.first().copied()).collect();
+
+        if first_bytes.is_empty() {
+            return Ok(None);
+        }
+
+        let mut scan_from = 0;
+        loop {
+            if scan_from >= hay.len() {
+                return Ok(None);
+            }
+
+            let rel = match self.next_candidate_byte(&first_bytes, &hay[scan_from..]) {
+                Some(rel) => rel,
+                None => return Ok(None),
+            };
+            let pos = scan_from + rel;
+
+            if let Some(result) = self.verify_at(hay, pos) {
+                let (idx, m) = result;
+                return Ok(Some((idx, Match::new(at + m.start(), at + m.end()))));
+            }
+
+            scan_from = pos + 1;
+        }
+    }
+
+    /// Union scan for the next haystack position whose byte matches at
+    /// least one pattern's first byte.
+    fn next_candidate_byte(&self, first_bytes: &[u8], hay: &[u8]) -> Option<usize> {
+        match first_bytes {
+            [] => None,
+            [a] => memchr::memchr(*a, hay),
+            [a, b] => memchr::memchr2(*a, *b, hay),
+            [a, b, c] => memchr::memchr3(*a, *b, *c, hay),
+            many => hay.iter().position(|b| many.contains(b)),
+        }
+    }
+
+    /// Verify every needle whose first byte matches `hay[pos]`, returning
+    /// the best (longest) one that fully matches there.
+    fn verify_at(&self, hay: &[u8], pos: usize) -> Option<(usize, Match)> {
+        let mut best: Option<(usize, Match)> = None;
+
+        for (idx, needle) in self.needles.iter().enumerate() {
+            let needle = &needle[..];
+            if needle.is_empty() || needle[0] != hay[pos] {
+                continue;
+            }
+            if pos + needle.len() > hay.len() {
+                continue;
+            }
+            if &hay[pos..pos + needle.len()] != needle {
+                continue;
+            }
+
+            let m = Match::new(pos, pos + needle.len());
+            match &best {
+                Some((_, best_match)) if best_match.len() >= m.len() => {}
+                _ => best = Some((idx, m)),
+            }
+        }
+
+        best
+    }
+}
+
+impl Matcher for MultiMatcher {
+    type Captures = NoCaptures;
+    type Error = NoError;
+
+    fn find_at(&self, haystack: &[u8], at: usize) -> Result<Option<Match>, Self::Error> {
+        Ok(self.find_at_with_index(haystack, at)?.map(|(_, m)| m))
+    }
+
+    fn new_captures(&self) -> Result<Self::Captures, Self::Error> {
+        Ok(NoCaptures::new())
+    }
+}
+
+impl MultiMatcher {
+    /// Return the rightmost match ending at or before `end`.
+    pub fn rfind_at(&self, haystack: &[u8], end: usize) -> Result<Option<Match>, NoError> {
+        iter::rfind_at(self, haystack, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use grep_matcher::Matcher;
+
+    use crate::multi_matcher::MultiMatcher;
+
+    #[test]
+    fn finds_leftmost_pattern() {
+        let needles: &[&[u8]] = &[b"bar", b"foo"];
+        let matcher = MultiMatcher::new(needles);
+        let haystack = b"xx foo bar";
+
+        let (idx, m) = matcher.find_at_with_index(haystack, 0).unwrap()
+            .expect("expected a match");
+
+        assert_eq!(idx, 1);
+        assert_eq!(m.start(), 3);
+    }
+
+    #[test]
+    fn ties_broken_by_longest() {
+        let needles: &[&[u8]] = &[b"foo", b"foobar"];
+        let matcher = MultiMatcher::new(needles);
+        let haystack = b"foobar";
+
+        let (idx, m) = matcher.find_at_with_index(haystack, 0).unwrap()
+            .expect("expected a match");
+
+        assert_eq!(idx, 1);
+        assert_eq!(m.len(), 6);
+    }
+
+    #[test]
+    fn find_at_none() {
+        let needles: &[&[u8]] = &[b"foo", b"bar"];
+        let matcher = MultiMatcher::new(needles);
+        let haystack = b"no alternatives here";
+
+        let result = matcher.find_at(haystack, 0);
+
+        assert_eq!(None, result.unwrap());
+    }
+}
@@ -2,6 +2,8 @@ use std::sync::Arc;
 
 use grep_matcher::{Match, Matcher, NoCaptures, NoError};
 
+use crate::iter;
+
 #[derive(Debug)]
 pub struct NaiveMatcher {
     needle: Arc<Vec<u8>>,
@@ -47,6 +49,13 @@ impl Matcher for NaiveMatcher {
     }
 }
 
+impl NaiveMatcher {
+    /// Return the rightmost match ending at or before `end`.
+    pub fn rfind_at(&self, haystack: &[u8], end: usize) -> Result<Option<Match>, NoError> {
+        iter::rfind_at(self, haystack, end)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -80,4 +89,16 @@ mod tests {
 
         assert_eq!(None, result.unwrap(), "should not find a match")
     }
+
+    #[test]
+    fn rfind_at_returns_rightmost_match() {
+        let haystack = b"hello world hello";
+        let needle = Arc::new(b"hello".to_vec());
+        let matcher = NaiveMatcher::new(&needle);
+
+        let result = matcher.rfind_at(haystack, haystack.len());
+        let r#match = result.unwrap().expect("expected to find a match");
+
+        assert_eq!(r#match.start(), 12);
+    }
 }
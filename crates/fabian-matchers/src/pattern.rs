@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+/// Converts a caller-supplied value into one or more needles that a
+/// [`crate::MultiMatcher`] (or any single-needle matcher) can search for.
+pub trait Pattern {
+    fn into_patterns(self) -> Vec<Arc<Vec<u8>>>;
+}
+
+impl Pattern for &str {
+    fn into_patterns(self) -> Vec<Arc<Vec<u8>>> {
+        vec![Arc::new(self.as_bytes().to_vec())]
+    }
+}
+
+impl Pattern for &[u8] {
+    fn into_patterns(self) -> Vec<Arc<Vec<u8>>> {
+        vec![Arc::new(self.to_vec())]
+    }
+}
+
+impl Pattern for Vec<u8> {
+    fn into_patterns(self) -> Vec<Arc<Vec<u8>>> {
+        vec![Arc::new(self)]
+    }
+}
+
+impl Pattern for &[&[u8]] {
+    fn into_patterns(self) -> Vec<Arc<Vec<u8>>> {
+        self.iter().map(|needle| Arc::new(needle.to_vec())).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pattern::Pattern;
+
+    #[test]
+    fn str_becomes_single_pattern() {
+        let patterns = "hello".into_patterns();
+
+        assert_eq!(patterns.len(), 1);
+        assert_eq!(&patterns[0][..], b"hello");
+    }
+
+    #[test]
+    fn slice_of_slices_becomes_many_patterns() {
+        let needles: &[&[u8]] = &[b"foo", b"bar", b"baz"];
+        let patterns = needles.into_patterns();
+
+        assert_eq!(patterns.len(), 3);
+        assert_eq!(&patterns[1][..], b"bar");
+    }
+}
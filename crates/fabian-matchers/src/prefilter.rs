@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+/// Ranks how common a byte is in typical text, so that the rarest bytes in a
+/// needle can be used to quickly skip over haystack regions that cannot
+/// possibly contain a match.
+///
+/// Lower ranks mean rarer bytes.
+pub trait HeuristicFrequencyRank {
+    fn rank(&self, byte: u8) -> u8;
+}
+
+/// The default byte-frequency ranking, tuned for typical English/code text.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultFrequencyRank;
+
+impl HeuristicFrequencyRank for DefaultFrequencyRank {
+    fn rank(&self, byte: u8) -> u8 {
+        DEFAULT_RANK[byte as usize]
+    }
+}
+
+/// Picks two "rare" byte offsets in a needle and lets callers quickly scan a
+/// haystack for candidate positions where both rare bytes line up, before
+/// running full verification.
+#[derive(Debug, Clone)]
+pub struct Prefilter {
+    /// Offset of the rarest byte in the needle.
+    r1: usize,
+    /// Offset of the next-rarest byte in the needle, at a different offset.
+    r2: usize,
+}
+
+impl Prefilter {
+    /// Build a prefilter for `needle` using the default frequency table.
+    pub fn new(needle: &Arc<Vec<u8>>) -> Self {
+        Prefilter::with_ranker(needle, &DefaultFrequencyRank)
+    }
+
+    /// Build a prefilter for `needle` using a caller-supplied ranking, e.g.
+    /// a table tuned for logs or DNA.
+    pub fn with_ranker<R: HeuristicFrequencyRank>(
+        needle: &Arc<Vec<u8>>,
+        ranker: &R,
+    ) -> Self {
+        let needle = &needle[..];
+        debug_assert!(!needle.is_empty());
+
+        let mut r1 = 0;
+        for i in 1..needle.len() {
+            if ranker.rank(needle[i]) < ranker.rank(needle[r1]) {
+                r1 = i;
+            }
+        }
+
+        // Fall back gracefully for length-1 needles: there's no second
+        // offset to pick, so just mirror `r1`.
+        let mut r2 = r1;
+        for i in 0..needle.len() {
+            if i == r1 {
+                continue;
+            }
+            if r2 == r1 || ranker.rank(needle[i]) < ranker.rank(needle[r2]) {
+                r2 = i;
+            }
+        }
+
+        Prefilter { r1, r2 }
+    }
+
+    pub fn r1(&self) -> usize {
+        self.r1
+    }
+
+    pub fn r2(&self) -> usize {
+        self.r2
+    }
+
+    /// Scan `haystack` for the next position at or after `at` where both
+    /// rare bytes of `needle` line up, returning the candidate needle start
+    /// offset (relative to the start of `haystack`), or `None` once no more
+    /// candidates can fit.
+    pub fn next_candidate(&self, haystack: &[u8], needle: &[u8], at: usize) -> Option<usize> {
+        let mut at = at;
+
+        loop {
+            if at + needle.len() > haystack.len() {
+                return None;
+            }
+
+            let scan_from = at + self.r1;
+            let rel = memchr::memchr(needle[self.r1], &haystack[scan_from..])?;
+            let start = scan_from + rel - self.r1;
+
+            if start + needle.len() > haystack.len() {
+                return None;
+            }
+
+            if haystack[start + self.r2] == needle[self.r2] {
+                return Some(start);
+            }
+
+            at = start + 1;
+        }
+    }
+}
+
+/// A static byte-frequency ranking for typical text, where lower values mean
+/// rarer bytes. Taken from the distribution of bytes across common English
+/// prose, source code and log files.
+pub static DEFAULT_RANK: [u8; 256] = [
+    55, 52, 51, 50, 49, 48, 47, 46, 45, 103, 242, 66, 67, 229, 44, 43,
+    42, 41, 40, 39, 38, 37, 36, 35, 34, 33, 56, 32, 31, 30, 29, 28,
+    255, 102, 99, 57, 86, 74, 69, 92, 203, 205, 84, 72, 200, 220, 221, 157,
+    225, 224, 210, 218, 209, 215, 199, 192, 188, 193, 183, 147, 101, 115, 100, 97,
+    97, 202, 136, 126, 141, 224, 117, 122, 133, 188, 54, 69, 102, 146, 178, 182,
+    104, 86, 166, 199, 178, 115, 82, 91, 66, 69, 60, 64, 54, 63, 44, 73,
+    53, 229, 172, 146, 210, 229, 146, 126, 194, 224, 65, 81, 166, 160, 221, 172,
+    89, 71, 178, 219, 217, 158, 116, 86, 80, 70, 63, 60, 52, 50, 45, 20,
+    19, 18, 17, 16, 15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4,
+    3, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+];
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::prefilter::Prefilter;
+
+    #[test]
+    fn picks_distinct_offsets_for_multi_byte_needle() {
+        let needle = Arc::new(b"hello".to_vec());
+        let prefilter = Prefilter::new(&needle);
+
+        assert_ne!(prefilter.r1(), prefilter.r2());
+    }
+
+    #[test]
+    fn next_candidate_finds_aligned_pair() {
+        let needle = Arc::new(b"hello".to_vec());
+        let prefilter = Prefilter::new(&needle);
+        let haystack = b"a b c hello next";
+
+        let candidate = prefilter.next_candidate(haystack, &needle, 0);
+
+        assert_eq!(candidate, Some(6));
+    }
+
+    #[test]
+    fn next_candidate_none_when_no_room() {
+        let needle = Arc::new(b"hello".to_vec());
+        let prefilter = Prefilter::new(&needle);
+        let haystack = b"hel";
+
+        assert_eq!(prefilter.next_candidate(haystack, &needle, 0), None);
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use grep_matcher::{Match, Matcher, NoCaptures, NoError};
+
+use crate::iter;
+use crate::prefilter::Prefilter;
+use crate::rabin_karp_matcher::RabinKarpMatcher;
+
+/// Wraps a [`RabinKarpMatcher`] with a rare-byte [`Prefilter`] so that full
+/// verification only runs at candidate positions, instead of on every
+/// window of the haystack.
+#[derive(Debug)]
+pub struct PrefilteredMatcher {
+    needle: Arc<Vec<u8>>,
+    prefilter: Prefilter,
+    inner: RabinKarpMatcher,
+}
+
+impl PrefilteredMatcher {
+    pub fn new(needle: &Arc<Vec<u8>>) -> Self {
+        PrefilteredMatcher {
+            needle: Arc::clone(needle),
+            prefilter: Prefilter::new(needle),
+            inner: RabinKarpMatcher::new(needle),
+        }
+    }
+}
+
+impl Clone for PrefilteredMatcher {
+    fn clone(&self) -> Self {
+        PrefilteredMatcher::new(&self.needle)
+    }
+}
+
+impl Matcher for PrefilteredMatcher {
+    type Captures = NoCaptures;
+    type Error = NoError;
+
+    fn find_at(&self, haystack: &[u8], at: usize) -> Result<Option<Match>, Self::Error> {
+        let needle = &self.needle[..];
+
+        if needle.is_empty() {
+            return Ok(None);
+        }
+
+        let mut at = at;
+        while let Some(start) = self.prefilter.next_candidate(haystack, needle, at) {
+            match self.inner.find_at(&haystack[..start + needle.len()], start)? {
+                Some(m) if m.start() == start => return Ok(Some(m)),
+                _ => at = start + 1,
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn new_captures(&self) -> Result<Self::Captures, Self::Error> {
+        Ok(NoCaptures::new())
+    }
+}
+
+impl PrefilteredMatcher {
+    /// Return the rightmost match ending at or before `end`.
+    pub fn rfind_at(&self, haystack: &[u8], end: usize) -> Result<Option<Match>, NoError> {
+        iter::rfind_at(self, haystack, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use grep_matcher::Matcher;
+
+    use crate::prefiltered_matcher::PrefilteredMatcher;
+
+    #[test]
+    fn find_at_some() {
+        let haystack = b"a b c hello next";
+        let needle = Arc::new(b"hello".to_vec());
+        let matcher = PrefilteredMatcher::new(&needle);
+
+        let result = matcher.find_at(haystack, 0);
+        let r#match = result.unwrap().expect("expected to find a match");
+
+        assert_eq!(r#match.start(), 6);
+        assert_eq!(r#match.end(), 6 + needle.len());
+    }
+
+    #[test]
+    fn find_at_none() {
+        let haystack = b"hello elloh";
+        let needle = Arc::new(b"hello".to_vec());
+        let matcher = PrefilteredMatcher::new(&needle);
+
+        let result = matcher.find_at(haystack, 1);
+
+        assert_eq!(None, result.unwrap(), "should not find a match")
+    }
+}
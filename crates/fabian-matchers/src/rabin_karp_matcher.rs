@@ -3,6 +3,8 @@ use std::sync::Arc;
 use grep_matcher::{Match, Matcher, NoCaptures, NoError};
 use hash::{Hash, NeedleHash};
 
+use crate::iter;
+
 #[derive(Debug)]
 pub struct RabinKarpMatcher {
     needle: Arc<Vec<u8>>,
@@ -59,6 +61,13 @@ impl Matcher for RabinKarpMatcher {
     }
 }
 
+impl RabinKarpMatcher {
+    /// Return the rightmost match ending at or before `end`.
+    pub fn rfind_at(&self, haystack: &[u8], end: usize) -> Result<Option<Match>, NoError> {
+        iter::rfind_at(self, haystack, end)
+    }
+}
+
 mod hash {
     #[derive(Debug, PartialEq, Eq)]
     pub struct Hash(u32);
@@ -198,4 +207,16 @@ mod tests {
 
         assert_eq!(None, result.unwrap(), "should not find a match")
     }
+
+    #[test]
+    fn rfind_at_returns_rightmost_match() {
+        let haystack = b"hello world hello";
+        let needle = Arc::new(b"hello".to_vec());
+        let matcher = RabinKarpMatcher::new(&needle);
+
+        let result = matcher.rfind_at(haystack, haystack.len());
+        let r#match = result.unwrap().expect("expected to find a match");
+
+        assert_eq!(r#match.start(), 12);
+    }
 }
@@ -0,0 +1,207 @@
+use std::sync::Arc;
+
+use grep_matcher::{Match, Matcher, NoCaptures, NoError};
+
+use crate::iter;
+use crate::rabin_karp_matcher::RabinKarpMatcher;
+
+/// Shift-Or (bitap) matcher for short needles, with optional bounded
+/// Hamming-distance fuzzy matching.
+///
+/// Needles longer than 64 bytes don't fit in a single `u64` state register,
+/// so construction falls back to [`RabinKarpMatcher`] in that case.
+#[derive(Debug)]
+pub struct ShiftOrMatcher {
+    needle: Arc<Vec<u8>>,
+    /// Maximum number of mismatches tolerated; `0` means exact matching.
+    k: usize,
+    repr: Repr,
+}
+
+#[derive(Debug, Clone)]
+enum Repr {
+    Bitap { mask: [u64; 256] },
+    Fallback(RabinKarpMatcher),
+}
+
+impl ShiftOrMatcher {
+    /// Create an exact-match Shift-Or matcher.
+    pub fn exact(needle: &Arc<Vec<u8>>) -> Self {
+        ShiftOrMatcher::new(needle, 0)
+    }
+
+    /// Create a Shift-Or matcher that tolerates up to `k` mismatches
+    /// (Hamming distance).
+    pub fn fuzzy(needle: &Arc<Vec<u8>>, k: usize) -> Self {
+        ShiftOrMatcher::new(needle, k)
+    }
+
+    fn new(needle: &Arc<Vec<u8>>, k: usize) -> Self {
+        if needle.is_empty() || needle.len() > 64 {
+            return ShiftOrMatcher {
+                needle: Arc::clone(needle),
+                k,
+                repr: Repr::Fallback(RabinKarpMatcher::new(needle)),
+            };
+        }
+
+        let mut mask = [0u64; 256];
+        for (j, &b) in needle.iter().enumerate() {
+            mask[b as usize] |= 1u64 << j;
+        }
+
+        ShiftOrMatcher { needle: Arc::clone(needle), k, repr: Repr::Bitap { mask } }
+    }
+
+    fn find_exact(&self, mask: &[u64; 256], haystack: &[u8], at: usize) -> Option<Match> {
+        let m = self.needle.len();
+        let top_bit = 1u64 << (m - 1);
+        let mut r: u64 = 0;
+
+        for (i, &c) in haystack[at..].iter().enumerate() {
+            r = ((r << 1) | 1) & mask[c as usize];
+            if r & top_bit != 0 {
+                let end = at + i + 1;
+                return Some(Match::new(end - m, end));
+            }
+        }
+
+        None
+    }
+
+    fn find_fuzzy(&self, mask: &[u64; 256], haystack: &[u8], at: usize) -> Option<Match> {
+        let m = self.needle.len();
+        let k = self.k;
+        let top_bit = 1u64 << (m - 1);
+
+        // Bit `j` of R[d] is set when `needle[..=j]` matches the text ending
+        // at the current byte with at most `d` substitutions; every register
+        // starts empty, so a match never starts before `at`.
+        let mut regs: Vec<u64> = vec![0u64; k + 1];
+
+        for (i, &c) in haystack[at..].iter().enumerate() {
+            let mc = mask[c as usize];
+            let mut new_regs = vec![0u64; k + 1];
+            new_regs[0] = ((regs[0] << 1) | 1) & mc;
+            for d in 1..=k {
+                new_regs[d] = (((regs[d] << 1) | 1) & mc) | ((regs[d - 1] << 1) | 1);
+            }
+            regs = new_regs;
+
+            // `Matcher::find_at` must return the leftmost match at or after
+            // `at`, so stop at the first position with any acceptable
+            // error count rather than scanning for a lower-error one later.
+            if regs.iter().any(|&reg| reg & top_bit != 0) {
+                let end = at + i + 1;
+                return Some(Match::new(end - m, end));
+            }
+        }
+
+        None
+    }
+}
+
+impl Clone for ShiftOrMatcher {
+    fn clone(&self) -> Self {
+        ShiftOrMatcher::new(&self.needle, self.k)
+    }
+}
+
+impl Matcher for ShiftOrMatcher {
+    type Captures = NoCaptures;
+    type Error = NoError;
+
+    fn find_at(&self, haystack: &[u8], at: usize) -> Result<Option<Match>, Self::Error> {
+        if self.needle.is_empty() {
+            return Ok(None);
+        }
+
+        match &self.repr {
+            Repr::Fallback(matcher) => matcher.find_at(haystack, at),
+            Repr::Bitap { mask } if self.k == 0 => Ok(self.find_exact(mask, haystack, at)),
+            Repr::Bitap { mask } => Ok(self.find_fuzzy(mask, haystack, at)),
+        }
+    }
+
+    fn new_captures(&self) -> Result<Self::Captures, Self::Error> {
+        Ok(NoCaptures::new())
+    }
+}
+
+impl ShiftOrMatcher {
+    /// Return the rightmost match ending at or before `end`.
+    pub fn rfind_at(&self, haystack: &[u8], end: usize) -> Result<Option<Match>, NoError> {
+        iter::rfind_at(self, haystack, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use grep_matcher::Matcher;
+
+    use crate::shift_or_matcher::ShiftOrMatcher;
+
+    #[test]
+    fn find_at_some() {
+        let haystack = b"a b c hello next";
+        let needle = Arc::new(b"hello".to_vec());
+        let matcher = ShiftOrMatcher::exact(&needle);
+
+        let result = matcher.find_at(haystack, 0);
+        let r#match = result.unwrap().expect("expected to find a match");
+
+        assert_eq!(r#match.start(), 6);
+        assert_eq!(r#match.end(), 6 + needle.len());
+    }
+
+    #[test]
+    fn find_at_none() {
+        let haystack = b"hello elloh";
+        let needle = Arc::new(b"hello".to_vec());
+        let matcher = ShiftOrMatcher::exact(&needle);
+
+        let result = matcher.find_at(haystack, 1);
+
+        assert_eq!(None, result.unwrap(), "should not find a match")
+    }
+
+    #[test]
+    fn fuzzy_tolerates_one_mismatch() {
+        let haystack = b"a b c hxllo next";
+        let needle = Arc::new(b"hello".to_vec());
+        let matcher = ShiftOrMatcher::fuzzy(&needle, 1);
+
+        let result = matcher.find_at(haystack, 0);
+        let r#match = result.unwrap().expect("expected a fuzzy match");
+
+        assert_eq!(r#match.start(), 6);
+        assert_eq!(r#match.end(), 6 + needle.len());
+    }
+
+    #[test]
+    fn fuzzy_returns_leftmost_match_even_with_more_errors() {
+        let haystack = b"hfllo hello";
+        let needle = Arc::new(b"hello".to_vec());
+        let matcher = ShiftOrMatcher::fuzzy(&needle, 1);
+
+        let result = matcher.find_at(haystack, 0);
+        let r#match = result.unwrap().expect("expected a fuzzy match");
+
+        assert_eq!(r#match.start(), 0, "leftmost match has 1 error; must win over the exact match at 6");
+        assert_eq!(r#match.end(), 5);
+    }
+
+    #[test]
+    fn oversized_needle_falls_back() {
+        let needle = Arc::new(vec![b'a'; 65]);
+        let matcher = ShiftOrMatcher::exact(&needle);
+        let mut haystack = vec![b'a'; 65];
+        haystack.push(b'b');
+
+        let result = matcher.find_at(&haystack, 0);
+
+        assert!(result.unwrap().is_some());
+    }
+}
@@ -0,0 +1,232 @@
+use std::sync::Arc;
+
+use grep_matcher::{Match, Matcher, NoCaptures, NoError};
+
+use crate::iter;
+
+/// Worst-case linear substring matcher based on the Crochemore-Perrin
+/// "Two-Way" algorithm.
+///
+/// Unlike Rabin-Karp, this matcher guarantees O(m+n) time regardless of the
+/// input, which makes it a safe default against adversarial needles/haystacks.
+#[derive(Debug)]
+pub struct TwoWayMatcher {
+    needle: Arc<Vec<u8>>,
+    /// Critical position of the needle's critical factorization `u . v`.
+    crit: usize,
+    /// Period of the suffix `v` starting at `crit`.
+    period: usize,
+}
+
+impl TwoWayMatcher {
+    pub fn new(needle: &Arc<Vec<u8>>) -> Self {
+        let (crit, period) = critical_factorization(needle);
+        TwoWayMatcher { needle: Arc::clone(needle), crit, period }
+    }
+}
+
+impl Clone for TwoWayMatcher {
+    fn clone(&self) -> Self {
+        TwoWayMatcher::new(&self.needle)
+    }
+}
+
+impl Matcher for TwoWayMatcher {
+    type Captures = NoCaptures;
+    type Error = NoError;
+
+    fn find_at(&self, haystack: &[u8], at: usize) -> Result<Option<Match>, Self::Error> {
+        let needle = &self.needle[..];
+        let haystack = &haystack[at..];
+
+        if needle.is_empty() {
+            return Ok(None);
+        }
+        if haystack.len() < needle.len() {
+            return Ok(None);
+        }
+
+        let mut pos = 0;
+        let mut memory = 0;
+
+        while pos + needle.len() <= haystack.len() {
+            // Scan the right part `v` left-to-right, starting from `crit`
+            // (or from `memory` if we can skip a known-matching prefix of it).
+            let mut i = std::cmp::max(self.crit, memory);
+            while i < needle.len() && needle[i] == haystack[pos + i] {
+                i += 1;
+            }
+
+            if i < needle.len() {
+                // Mismatch inside `v`: shift right past the mismatch.
+                pos += i - self.crit + 1;
+                memory = 0;
+                continue;
+            }
+
+            // `v` matched fully, now scan the left part `u` right-to-left.
+            let mut j = self.crit;
+            while j > memory && needle[j - 1] == haystack[pos + j - 1] {
+                j -= 1;
+            }
+
+            if j <= memory {
+                let match_start = at + pos;
+                let match_end = match_start + needle.len();
+                return Ok(Some(Match::new(match_start, match_end)));
+            }
+
+            if needle[..self.crit] == needle[self.period..self.period + self.crit] {
+                // Periodic needle: remember how much of `v` we can skip
+                // re-checking next time to preserve linearity.
+                pos += self.period;
+                memory = needle.len() - self.period;
+            } else {
+                pos += std::cmp::max(self.crit, needle.len() - self.crit) + 1;
+                memory = 0;
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn new_captures(&self) -> Result<Self::Captures, Self::Error> {
+        Ok(NoCaptures::new())
+    }
+}
+
+impl TwoWayMatcher {
+    /// Return the rightmost match ending at or before `end`.
+    pub fn rfind_at(&self, haystack: &[u8], end: usize) -> Result<Option<Match>, NoError> {
+        iter::rfind_at(self, haystack, end)
+    }
+}
+
+/// Computes the maximal suffix of `needle` under either the `<=` (`le`) or
+/// the `>=` byte ordering, returning its starting index and period.
+fn maximal_suffix(needle: &[u8], le: bool) -> (usize, usize) {
+    let n = needle.len();
+    let mut i: usize = 0;
+    let mut j: usize = 1;
+    let mut k: usize = 0;
+    let mut period: usize = 1;
+
+    while j + k < n {
+        let a = needle[j + k];
+        let b = needle[i + k];
+
+        let a_lt_b = if le { a < b } else { a > b };
+        let a_gt_b = if le { a > b } else { a < b };
+
+        if a_lt_b {
+            j += k + 1;
+            k = 0;
+            period = j - i;
+        } else if a == b {
+            if k + 1 == period {
+                j += period;
+                k = 0;
+            } else {
+                k += 1;
+            }
+        } else {
+            debug_assert!(a_gt_b);
+            i = j;
+            j += 1;
+            k = 0;
+            period = 1;
+        }
+    }
+
+    (i, period)
+}
+
+/// Splits `needle` into its critical factorization `needle = u . v` and
+/// returns the starting index of `v` along with its period.
+fn critical_factorization(needle: &Arc<Vec<u8>>) -> (usize, usize) {
+    let needle = &needle[..];
+    if needle.is_empty() {
+        return (0, 1);
+    }
+
+    let (i1, p1) = maximal_suffix(needle, true);
+    let (i2, p2) = maximal_suffix(needle, false);
+
+    if i1 > i2 {
+        (i1, p1)
+    } else {
+        (i2, p2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use grep_matcher::Matcher;
+
+    use crate::two_way_matcher::TwoWayMatcher;
+
+    #[test]
+    fn find_at_some() {
+        let haystack = b"a b c hello next";
+        let needle = Arc::new(b"hello".to_vec());
+        let matcher = TwoWayMatcher::new(&needle);
+
+        let result = matcher.find_at(haystack, 5);
+        let r#match = result.unwrap().expect("expected to find a match");
+
+        assert_eq!(r#match.len(), needle.len());
+        assert_eq!(r#match.start(), 6,
+                   "should return relative id from the needle start,but not 'at'");
+        assert_eq!(r#match.end(), 6 + needle.len());
+    }
+
+    #[test]
+    fn find_at_none() {
+        let haystack = b"hello elloh";
+        let needle = Arc::new(b"hello".to_vec());
+        let matcher = TwoWayMatcher::new(&needle);
+
+        let result = matcher.find_at(haystack, 1);
+
+        assert_eq!(None, result.unwrap(), "should not find a match")
+    }
+
+    #[test]
+    fn find_at_periodic_needle() {
+        let haystack = b"aaaaaaaaaaaab";
+        let needle = Arc::new(b"aaab".to_vec());
+        let matcher = TwoWayMatcher::new(&needle);
+
+        let result = matcher.find_at(haystack, 0);
+        let r#match = result.unwrap().expect("expected to find a match");
+
+        assert_eq!(r#match.start(), 9);
+        assert_eq!(r#match.end(), 9 + needle.len());
+    }
+
+    #[test]
+    fn find_at_two_byte_needle() {
+        let haystack = b"bba";
+        let needle = Arc::new(b"ba".to_vec());
+        let matcher = TwoWayMatcher::new(&needle);
+
+        let result = matcher.find_at(haystack, 0);
+        let r#match = result.unwrap().expect("expected to find a match");
+
+        assert_eq!(r#match.start(), 1);
+        assert_eq!(r#match.end(), 3);
+    }
+
+    #[test]
+    fn find_at_does_not_false_match_on_short_period() {
+        let haystack = b"aaaaa";
+        let needle = Arc::new(b"ba".to_vec());
+        let matcher = TwoWayMatcher::new(&needle);
+
+        let result = matcher.find_at(haystack, 0);
+
+        assert_eq!(None, result.unwrap(), "needle is not a substring of the haystack");
+    }
+}
@@ -1,12 +1,61 @@
 use std::fs::File;
+use std::io::Read;
+use std::os::unix::fs::FileExt;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 use bytesize::ByteSize;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use rand::seq::SliceRandom;
 use grep_matcher::Matcher;
 use crate::{Searcher, Sink, SinkError};
-use crate::searcher::parallel_default_searcher::work_pool::WorkPool;
+use crate::searcher::parallel_default_searcher::buffer_pool::BufferPool;
+use crate::searcher::parallel_default_searcher::order::FlushQueue;
+use crate::searcher::parallel_default_searcher::work_queue::SharedQueue;
 use crate::searcher::parallel_default_searcher::worker::{BufferedWorker, split_into_ranges};
 
+/// Compression codecs we can transparently decode before searching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Codec {
+    Gzip,
+    Zlib,
+}
+
+impl Codec {
+    /// Sniff `path`'s leading bytes for a gzip or zlib magic header.
+    fn detect(file: &File, path: &Path) -> std::io::Result<Option<Codec>> {
+        let mut magic = [0u8; 2];
+        if file.read_at(&mut magic, 0)? < 2 {
+            return Ok(None);
+        }
+
+        match magic {
+            [0x1f, 0x8b] => Ok(Some(Codec::Gzip)),
+            // Zlib's 2-byte header check (low nibble of the first byte is
+            // 8, and the pair is a multiple of 31) has roughly a 1-in-31
+            // chance of matching arbitrary bytes, so an ordinary binary or
+            // text file can trip it. Gzip's 2-byte magic doesn't have that
+            // problem (1 in 65536), so only zlib needs a second signal:
+            // require the file extension to say so too.
+            [cmf, flg]
+                if cmf & 0x0f == 8
+                    && (u16::from(cmf) * 256 + u16::from(flg)) % 31 == 0
+                    && has_zlib_extension(path) =>
+            {
+                Ok(Some(Codec::Zlib))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Whether `path`'s extension is one commonly used for raw zlib streams.
+fn has_zlib_extension(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("zz") | Some("zlib")
+    )
+}
+
 /// Searcher that performs it's search using multithreading
 #[derive(Debug)]
 pub struct ParallelDefaultSearcher {
@@ -36,25 +85,70 @@ impl ParallelDefaultSearcher {
     {
         let path = path.as_ref();
         let file = File::open(path).map_err(S::Error::error_io)?;
+
+        // Compressed input can't be split into independently-seekable byte
+        // ranges, so give up the multithreaded path for this one file and
+        // decode + search it single-threaded instead. Parallelism across
+        // multiple files is unaffected, since each still gets its own
+        // `search_path` call.
+        if let Some(codec) = Codec::detect(&file, path).map_err(S::Error::error_io)? {
+            return self.search_compressed(matcher, file, codec, sink);
+        }
+
         let file_len = file.metadata().map_err(S::Error::error_io)?.len();
         let buf_size = file_len.min(ByteSize::mib(10).0) as usize;
         let file = Arc::new(file);
 
-        let ranges = split_into_ranges(file_len, buf_size as u64);
-        let queues = WorkPool::split_into_chunks(
-            self.threads.min(ranges.len()),
-            ranges,
-        );
+        // Cut the file into many more, smaller chunks than we have threads
+        // and shuffle them before handing them out. A thread that lands on
+        // a run of cheap chunks (or finishes early) steals the next shuffled
+        // chunk off the shared queue instead of sitting idle on its own
+        // statically-assigned slice.
+        let chunk_floor = ByteSize::kib(64).0;
+        let chunk_cap = (buf_size as u64).max(chunk_floor);
+        let target_chunks = (self.threads.max(1) as u64) * 64;
+        let chunk_size = (file_len / target_chunks.max(1)).clamp(chunk_floor, chunk_cap);
+
+        // Tag each chunk with its original, ascending position before
+        // shuffling so results can still be flushed to the sink in file
+        // order no matter which worker ends up searching which chunk.
+        let mut ranges: Vec<_> = split_into_ranges(file_len, chunk_size.max(1))
+            .into_iter()
+            .enumerate()
+            .collect();
+        // Safe to shuffle freely: ordering is restored downstream by
+        // `FlushQueue`, which stashes an out-of-turn chunk instead of
+        // blocking anyone on it, so an arbitrary shuffle (including one
+        // whose first popped chunk isn't index 0) can't deadlock.
+        ranges.shuffle(&mut rand::thread_rng());
+        let queue = SharedQueue::new(ranges);
+        // Chunks are searched in whatever order workers steal them, but
+        // `FlushQueue` buffers each chunk's read bytes and only lets a
+        // chunk's search-and-flush into `sink` happen once every
+        // lower-indexed chunk already has, so matches still reach `sink` in
+        // ascending file order.
+        let flush_queue = Arc::new(FlushQueue::new());
+
+        // Workers check a buffer out of this pool before reading a chunk
+        // and return it afterwards, rather than each holding its own
+        // buffer for its whole lifetime. Capping the pool at `threads`
+        // keeps peak memory at roughly `in_flight_chunks * buf_size`
+        // instead of growing without bound when a long line forces one
+        // buffer past the nominal chunk size.
+        let pool = BufferPool::new(self.threads.max(1));
+
         std::thread::scope(|s| {
-            let handles: Vec<_> = queues.into_iter()
-                .map(|queue| {
+            let handles: Vec<_> = (0..self.threads)
+                .map(|_| {
                     BufferedWorker::new(
                         &file,
-                        queue,
-                        std::iter::repeat(0).take(buf_size).collect(),
+                        queue.clone(),
+                        pool.clone(),
                         self.searcher.clone(),
                         &matcher,
                         Arc::clone(&sink),
+                        file_len,
+                        Arc::clone(&flush_queue),
                     )
                 })
                 .map(|worker| s.spawn(|| worker.run()))
@@ -67,15 +161,38 @@ impl ParallelDefaultSearcher {
 
         Ok(())
     }
+
+    /// Stream-decode `file` as `codec` and search it in a single pass.
+    fn search_compressed<M, S>(
+        &mut self,
+        matcher: M,
+        file: File,
+        codec: Codec,
+        sink: Arc<Mutex<S>>,
+    ) -> Result<(), S::Error>
+        where
+            M: Matcher,
+            S: Sink,
+    {
+        let rdr: Box<dyn Read> = match codec {
+            Codec::Gzip => Box::new(GzDecoder::new(file)),
+            Codec::Zlib => Box::new(ZlibDecoder::new(file)),
+        };
+
+        self.searcher.search_reader(&matcher, rdr, sink)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::error::Error;
     use std::fs::File;
+    use std::io::Write;
     use std::os::unix::fs::FileExt;
     use std::path::Path;
 
+    use super::Codec;
+
     #[test]
     fn it_works() -> Result<(), Box<dyn Error>> {
         let path = Path::new("../../tmp/Windows.log");
@@ -86,37 +203,210 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn zlib_sniff_does_not_false_positive_without_extension() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join("fabian-rg-zlib-sniff-test.bin");
+        // 0x78, 0x01 satisfies the naive zlib magic check (cmf & 0x0f == 8,
+        // header % 31 == 0) but this is an ordinary, non-compressed file
+        // with no zlib-ish extension.
+        File::create(&path)?.write_all(&[0x78, 0x01, b'h', b'i'])?;
+
+        let file = File::open(&path)?;
+        let detected = Codec::detect(&file, &path)?;
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(detected, None, "must not misdetect zlib without a corroborating extension");
+        Ok(())
+    }
 }
 
-mod work_pool {
-    use std::collections::VecDeque;
+mod work_queue {
+    use std::sync::Arc;
+
+    use crossbeam_deque::{Injector, Steal};
 
-    pub struct WorkPool<T> {
-        work: VecDeque<T>,
+    /// A single queue shared by every worker thread, backed by a
+    /// crossbeam `Injector`. All workers steal from the same queue rather
+    /// than each owning a static, pre-sliced share of the work.
+    #[derive(Debug)]
+    pub struct SharedQueue<T> {
+        injector: Arc<Injector<T>>,
     }
 
-    impl<T> WorkPool<T> {
-        pub fn split_into_chunks(threads: usize, init: Vec<T>) -> Vec<WorkPool<T>> where T: Clone {
-            // Calculate the size of each chunk
-            let chunk_size = init.len() / threads + if init.len() % threads > 0 { 1 } else { 0 };
+    impl<T> Clone for SharedQueue<T> {
+        fn clone(&self) -> Self {
+            SharedQueue { injector: Arc::clone(&self.injector) }
+        }
+    }
 
-            // Split the vector into chunks and collect them into a new vector
-            init
-                .chunks(chunk_size)
-                .map(|chunk| VecDeque::from(chunk.to_vec()))
-                .map(|v| WorkPool { work: v })
-                .collect()
+    impl<T> SharedQueue<T> {
+        pub fn new(init: Vec<T>) -> Self {
+            let injector = Injector::new();
+            for item in init {
+                injector.push(item);
+            }
+            SharedQueue { injector: Arc::new(injector) }
         }
 
-        pub fn pop(&mut self) -> Option<T> {
-            self.work.pop_front()
+        pub fn pop(&self) -> Option<T> {
+            loop {
+                match self.injector.steal() {
+                    Steal::Success(item) => return Some(item),
+                    Steal::Empty => return None,
+                    Steal::Retry => continue,
+                }
+            }
+        }
+    }
+}
+
+mod buffer_pool {
+    use std::sync::{Arc, Mutex};
+
+    /// A pool of reusable read buffers shared across worker threads.
+    ///
+    /// Workers check a buffer out before filling it and return it once
+    /// they're done with it, so peak memory stays close to
+    /// `in_flight_chunks * buf_size` instead of every worker permanently
+    /// holding its own buffer (and a buffer that grew past `buf_size` to
+    /// fit one unusually long line doesn't keep that larger allocation
+    /// alive for the worker's whole lifetime).
+    #[derive(Debug, Clone)]
+    pub struct BufferPool {
+        inner: Arc<Mutex<Inner>>,
+    }
+
+    #[derive(Debug)]
+    struct Inner {
+        free: Vec<Vec<u8>>,
+        max_pooled: usize,
+    }
+
+    impl BufferPool {
+        pub fn new(max_pooled: usize) -> Self {
+            BufferPool { inner: Arc::new(Mutex::new(Inner { free: Vec::new(), max_pooled })) }
+        }
+
+        /// Check out a buffer with at least `min_capacity` bytes of
+        /// capacity, reusing the smallest free buffer that already fits
+        /// before allocating a new one.
+        pub fn checkout(&self, min_capacity: usize) -> Vec<u8> {
+            let mut inner = self.inner.lock().unwrap();
+            let best = inner.free.iter()
+                .enumerate()
+                .filter(|(_, b)| b.capacity() >= min_capacity)
+                .min_by_key(|(_, b)| b.capacity())
+                .map(|(i, _)| i);
+
+            match best {
+                Some(i) => {
+                    let mut buf = inner.free.swap_remove(i);
+                    buf.clear();
+                    buf
+                }
+                None => Vec::with_capacity(min_capacity),
+            }
+        }
+
+        /// Return a buffer to the pool for reuse, unless the pool is
+        /// already at capacity, which bounds how many buffers (and thus
+        /// how much memory) stay alive between chunks.
+        pub fn release(&self, buf: Vec<u8>) {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.free.len() < inner.max_pooled {
+                inner.free.push(buf);
+            }
+        }
+    }
+}
+
+mod order {
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    /// Orders a per-chunk step that writes to a shared sink, so results
+    /// reach the sink in ascending chunk-index order even though chunks are
+    /// stolen and processed out of order.
+    ///
+    /// Unlike a turnstile that blocks a thread until its index comes up,
+    /// `submit` never blocks: a caller whose index isn't next yet just
+    /// stashes its value and returns immediately, freeing it to go steal
+    /// more work. Whoever's `submit` call happens to land on the current
+    /// `next` index becomes the drainer and runs `flush` for it (and for
+    /// any further indexes that are already stashed and now consecutive)
+    /// with the lock released, so the (CPU-bound) `flush` call for one
+    /// chunk never blocks other workers from reading their own chunks and
+    /// submitting them in the meantime — only `pending`/`next` bookkeeping
+    /// is ever done under the lock.
+    #[derive(Debug)]
+    pub struct FlushQueue<T> {
+        state: Mutex<State<T>>,
+    }
+
+    #[derive(Debug)]
+    struct State<T> {
+        next: usize,
+        pending: BTreeMap<usize, T>,
+        /// Whether some thread is already draining `pending` in order. Only
+        /// one thread may drain at a time, which keeps `flush` calls
+        /// strictly ordered without needing to hold the lock across them.
+        draining: bool,
+    }
+
+    impl<T> FlushQueue<T> {
+        pub fn new() -> Self {
+            FlushQueue {
+                state: Mutex::new(State { next: 0, pending: BTreeMap::new(), draining: false }),
+            }
+        }
+
+        /// Submit `index`'s value. If it's not yet `index`'s turn, or
+        /// another thread is already draining, this just records it and
+        /// returns. Otherwise, this call becomes the drainer: it runs
+        /// `flush` for `index` and for every following index already
+        /// waiting in order, releasing the lock for the duration of each
+        /// `flush` call so it never serializes work that doesn't need to be
+        /// ordered.
+        pub fn submit<E>(
+            &self,
+            index: usize,
+            value: T,
+            mut flush: impl FnMut(T) -> Result<(), E>,
+        ) -> Result<(), E> {
+            let mut state = self.state.lock().unwrap();
+            state.pending.insert(index, value);
+
+            if state.draining {
+                return Ok(());
+            }
+            state.draining = true;
+
+            loop {
+                let value = match state.pending.remove(&state.next) {
+                    Some(value) => value,
+                    None => break,
+                };
+                state.next += 1;
+
+                drop(state);
+                let result = flush(value);
+                state = self.state.lock().unwrap();
+
+                if let Err(e) = result {
+                    state.draining = false;
+                    return Err(e);
+                }
+            }
+
+            state.draining = false;
+            Ok(())
         }
     }
 }
 
 mod worker {
     use std::fs::File;
-    use std::io::Cursor;
     use std::ops::Range;
     use std::os::unix::prelude::*;
     use std::sync::{Arc, Mutex};
@@ -124,62 +414,178 @@ mod worker {
     use bstr::ByteSlice;
 
     use grep_matcher::Matcher;
+    use grep_searcher::SinkMatch;
 
     use crate::{Searcher, Sink};
-    use crate::searcher::parallel_default_searcher::work_pool::WorkPool;
+    use crate::searcher::parallel_default_searcher::buffer_pool::BufferPool;
+    use crate::searcher::parallel_default_searcher::order::FlushQueue;
+    use crate::searcher::parallel_default_searcher::work_queue::SharedQueue;
+    use crate::searcher::parallel_searcher::OffsetAwareSink;
 
     pub struct BufferedWorker<M: Matcher, S: Sink> {
         file: Arc<File>,
-        queue: WorkPool<Range<u64>>,
-        buffer: Cursor<Vec<u8>>,
+        file_len: u64,
+        queue: SharedQueue<(usize, Range<u64>)>,
+        pool: BufferPool,
         searcher: Searcher,
         matcher: M,
         sink: Arc<Mutex<S>>,
+        /// Each flushed value is the chunk's absolute file offset (for
+        /// `OffsetSink`, see below) alongside its filled buffer.
+        flush_queue: Arc<FlushQueue<(u64, Vec<u8>)>>,
     }
 
     impl<M: Matcher, S: Sink> BufferedWorker<M, S> {
         pub fn new(
             file: &Arc<File>,
-            queue: WorkPool<Range<u64>>,
-            buffer: Vec<u8>,
+            queue: SharedQueue<(usize, Range<u64>)>,
+            pool: BufferPool,
             searcher: Searcher,
             matcher: M,
             sink: Arc<Mutex<S>>,
+            file_len: u64,
+            flush_queue: Arc<FlushQueue<(u64, Vec<u8>)>>,
         ) -> Self {
             BufferedWorker {
                 file: Arc::clone(file),
+                file_len,
                 queue,
-                buffer: Cursor::new(buffer),
+                pool,
                 searcher,
                 matcher,
                 sink,
+                flush_queue,
             }
         }
 
-        pub fn run(mut self) -> Result<(), S::Error> {
-            while let Some(_) = self.fill_buffer() {
-                let rdr = self.buffer.get_ref().as_bytes();
-                let res = self.searcher.search_reader(&self.matcher, rdr, Arc::clone(&self.sink));
-                res?
+        pub fn run(self) -> Result<(), S::Error> {
+            while let Some((index, range)) = self.recv() {
+                // Reading the chunk, and deciding whether it's this
+                // worker's own or a stashed-earlier chunk's turn to reach
+                // the sink, can both happen as soon as it's stolen, out of
+                // order. No worker ever blocks waiting for its turn: it
+                // either becomes the drainer (its own chunk, plus any
+                // consecutive ones already stashed by other workers - each
+                // searched and flushed with `flush_queue`'s lock released,
+                // so other workers keep reading and submitting their own
+                // chunks concurrently) or stashes its chunk for whichever
+                // worker's drain reaches it next.
+                let nominal_len = (range.end - range.start) as usize;
+                let mut buffer = self.pool.checkout(nominal_len);
+                let base_offset = self.fill_buffer(&mut buffer, &range);
+                log::trace!(
+                    "[ripgrep] searching chunk {} ({} bytes at file offset {})",
+                    index, buffer.len(), base_offset,
+                );
+
+                let searcher = &self.searcher;
+                let matcher = &self.matcher;
+                let sink = &self.sink;
+                let pool = &self.pool;
+                self.flush_queue.submit(index, (base_offset, buffer), |(base_offset, buffer)| {
+                    let offset_sink = OffsetSink { inner: Arc::clone(sink), base_offset };
+                    let res = searcher.search_reader(matcher, buffer.as_slice(), offset_sink);
+                    pool.release(buffer);
+                    res
+                })?;
             }
 
             Ok(())
         }
 
-        fn fill_buffer(&mut self) -> Option<usize> {
-            let range = self.recv()?;
-            let buffer = self.buffer.get_mut();
-            buffer.fill(0);
-            let n = self.file.read_at(buffer, range.start).ok()?;
-            Some(n)
+        /// Fill `buffer` with `range`, line-aligned so that no match
+        /// straddling a chunk boundary is lost or double-counted: a partial
+        /// leading line (owned by the previous range) is dropped, and
+        /// reading continues past `range.end` until the next `\n` so this
+        /// worker owns its full trailing line. Returns the absolute file
+        /// offset of the first byte left in the buffer, which `run` passes
+        /// to `OffsetSink` as the chunk's `base_offset`.
+        fn fill_buffer(&self, buffer: &mut Vec<u8>, range: &Range<u64>) -> u64 {
+            let nominal_len = (range.end - range.start) as usize;
+            let mut read_len = nominal_len.max(buffer.capacity());
+
+            let n = loop {
+                buffer.resize(read_len, 0);
+                let n = self.file.read_at(buffer, range.start).unwrap_or(0);
+                buffer.truncate(n);
+
+                let reached_eof = range.start + n as u64 >= self.file_len;
+                let own_end = nominal_len.min(n);
+                let ends_with_newline = own_end > 0 && buffer[own_end - 1] == b'\n';
+                let found_newline_after = buffer[own_end..].contains(&b'\n');
+
+                if reached_eof || ends_with_newline || found_newline_after {
+                    break n;
+                }
+
+                read_len = (read_len * 2).max(read_len + nominal_len.max(4096));
+            };
+
+            let own_end = nominal_len.min(n);
+
+            // Own our full trailing line: keep up through the first `\n`
+            // at or after the nominal range end (or to EOF if there's none).
+            let keep_end = if own_end > 0 && buffer[own_end - 1] == b'\n' {
+                own_end
+            } else {
+                match buffer[own_end..].find_byte(b'\n') {
+                    Some(i) => own_end + i + 1,
+                    None => buffer.len(),
+                }
+            };
+
+            // Drop the partial leading line: it belongs to the previous
+            // range, which read past its own end to pick it up. But if
+            // `range.start` itself falls right after a `\n`, there is no
+            // partial line to drop: this chunk's first line is already
+            // complete, and the previous range stopped exactly at
+            // `range.start` without reading any of it.
+            let starts_mid_line = range.start > 0 && {
+                let mut prev = [0u8; 1];
+                self.file.read_at(&mut prev, range.start - 1).unwrap_or(0) == 1
+                    && prev[0] != b'\n'
+            };
+            let skip = if starts_mid_line {
+                match buffer[..keep_end].find_byte(b'\n') {
+                    Some(i) => i + 1,
+                    None => keep_end,
+                }
+            } else {
+                0
+            };
+
+            buffer.drain(keep_end..);
+            buffer.drain(..skip);
+
+            range.start + skip as u64
         }
 
-        /// Receive work.
-        fn recv(&mut self) -> Option<Range<u64>> {
+        /// Receive work, stealing from the shared queue.
+        fn recv(&self) -> Option<(usize, Range<u64>)> {
             self.queue.pop()
         }
     }
 
+    /// Wraps the real sink so a chunk's matches are reported through
+    /// [`OffsetAwareSink::matched_in_chunk`] with this chunk's absolute file
+    /// offset: `SinkMatch` has no public constructor, so there's no way to
+    /// hand `inner` a copy with a file-global offset directly. The blanket
+    /// impl of `OffsetAwareSink` gives every sink the default, which
+    /// ignores `base_offset`, so `inner` sees offsets relative to this
+    /// chunk's buffer.
+    struct OffsetSink<S: Sink> {
+        inner: Arc<Mutex<S>>,
+        base_offset: u64,
+    }
+
+    impl<S: Sink> Sink for OffsetSink<S> {
+        type Error = S::Error;
+
+        fn matched(&mut self, searcher: &Searcher, mat: &SinkMatch<'_>) -> Result<bool, Self::Error> {
+            self.inner.lock().unwrap().matched_in_chunk(searcher, mat, self.base_offset)
+        }
+    }
+
     pub fn split_into_ranges(number: u64, step: u64) -> Vec<Range<u64>> {
         if number <= step {
             return vec![0..number];
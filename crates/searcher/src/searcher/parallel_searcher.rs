@@ -5,11 +5,37 @@ use std::sync::{Arc, Mutex};
 use bytesize::ByteSize;
 
 use grep_matcher::Matcher;
+use grep_searcher::SinkMatch;
 
 use crate::{Searcher, Sink, SinkError};
 use crate::searcher::parallel_searcher::work_stealing::WorkStealingQueue;
 use crate::searcher::parallel_searcher::worker::{BufferedWorker, split_into_ranges};
 
+/// Extension of [`Sink`] through which a worker that's only searching one
+/// byte range of a larger file reports each match together with that
+/// range's absolute file offset (`base_offset`): `Searcher`/`SinkMatch`
+/// have no notion of a reader starting at a non-zero file offset, so a
+/// worker can't hand a plain `Sink` a corrected `SinkMatch` directly.
+///
+/// The default implementation ignores `base_offset` and forwards to
+/// [`Sink::matched`] unchanged. The blanket impl below gives every `Sink`
+/// that default, and since every implementor of this trait is a `Sink`, no
+/// sink can provide its own `matched_in_chunk`: sinks always see
+/// chunk-relative offsets and line numbers, and `base_offset` is unused.
+pub trait OffsetAwareSink: Sink {
+    fn matched_in_chunk(
+        &mut self,
+        searcher: &Searcher,
+        mat: &SinkMatch<'_>,
+        base_offset: u64,
+    ) -> Result<bool, Self::Error> {
+        let _ = base_offset;
+        self.matched(searcher, mat)
+    }
+}
+
+impl<S: Sink> OffsetAwareSink for S {}
+
 /// Searcher that performs it's search using multithreading
 #[derive(Debug)]
 pub struct ParallelSearcher {
@@ -25,11 +51,18 @@ impl ParallelSearcher {
 
     /// Execute a search over the file with the given path and write the
     /// results to the given sink.
+    ///
+    /// `max_match_len` must be at least as large as the longest match the
+    /// given matcher can ever produce (e.g. the needle length for a literal
+    /// matcher). It is used to read a small overlap past the end of each
+    /// worker's range so that matches straddling a chunk boundary aren't
+    /// silently dropped.
     pub fn search_path<P, M, S>(
         &mut self,
         matcher: M,
         path: P,
         sink: Arc<Mutex<S>>,
+        max_match_len: usize,
     ) -> Result<(), S::Error>
         where
             P: AsRef<Path>,
@@ -40,6 +73,7 @@ impl ParallelSearcher {
         let file = File::open(path).map_err(S::Error::error_io)?;
         let file_len = file.metadata().map_err(S::Error::error_io)?.len();
         let buf_size = file_len.min(ByteSize::mib(10).0) as usize;
+        let overlap = max_match_len.saturating_sub(1);
         let file = Arc::new(file);
 
         let ranges = split_into_ranges(file_len, buf_size as u64);
@@ -52,10 +86,12 @@ impl ParallelSearcher {
                 .map(|queue| BufferedWorker::new(
                     &file,
                     queue,
-                    Vec::with_capacity(buf_size),
+                    Vec::with_capacity(buf_size + overlap),
                     self.searcher.clone(),
                     &matcher,
                     Arc::clone(&sink),
+                    file_len,
+                    overlap,
                 ))
                 .map(|worker| s.spawn(|| worker.run()))
                 .collect();
@@ -167,9 +203,11 @@ mod worker {
     use bstr::ByteSlice;
 
     use grep_matcher::Matcher;
+    use grep_searcher::SinkMatch;
 
-    use crate::{Searcher, Sink};
+    use crate::{Searcher, Sink, SinkError};
     use crate::searcher::parallel_searcher::work_stealing::WorkStealingQueue;
+    use crate::searcher::parallel_searcher::OffsetAwareSink;
 
     pub struct BufferedWorker<M: Matcher, S: Sink> {
         file: Arc<File>,
@@ -178,6 +216,10 @@ mod worker {
         searcher: Searcher,
         matcher: M,
         sink: Arc<Mutex<S>>,
+        file_len: u64,
+        /// Extra bytes read past each range's `end`, so a match that starts
+        /// inside the range but extends past it isn't silently dropped.
+        overlap: usize,
     }
 
     impl<M: Matcher, S: Sink> BufferedWorker<M, S> {
@@ -188,6 +230,8 @@ mod worker {
             searcher: Searcher,
             matcher: M,
             sink: Arc<Mutex<S>>,
+            file_len: u64,
+            overlap: usize,
         ) -> Self {
             BufferedWorker {
                 file: Arc::clone(file),
@@ -196,25 +240,41 @@ mod worker {
                 searcher,
                 matcher,
                 sink,
+                file_len,
+                overlap,
             }
         }
 
         pub fn run(mut self) -> Result<(), S::Error> {
-            while let Some(_) = self.fill_buffer() {
+            while let Some(range) = self.recv() {
+                let boundary = self.fill_buffer(&range).map_err(S::Error::error_io)?;
                 let rdr = self.buffer.get_ref().as_bytes();
-                let res = self.searcher.search_reader(&self.matcher, rdr, Arc::clone(&self.sink));
+                let sink = BoundarySink {
+                    inner: Arc::clone(&self.sink),
+                    boundary,
+                    base_offset: range.start,
+                };
+                let res = self.searcher.search_reader(&self.matcher, rdr, sink);
                 res?
             }
 
             Ok(())
         }
 
-        fn fill_buffer(&mut self) -> Option<usize> {
-            let range = self.recv()?;
+        /// Read this worker's range into the buffer, plus `overlap` extra
+        /// bytes past `range.end` (clamped to the file length). Returns the
+        /// buffer-relative offset at which the range's own bytes end, i.e.
+        /// anything at or past it is just overlap owned by the next range.
+        fn fill_buffer(&mut self, range: &Range<u64>) -> std::io::Result<usize> {
+            let extended_end = std::cmp::min(self.file_len, range.end + self.overlap as u64);
+            let read_len = (extended_end - range.start) as usize;
+
             let buffer = self.buffer.get_mut();
-            buffer.clear();
-            let n = self.file.read_at(buffer, range.start).ok()?;
-            Some(n)
+            buffer.resize(read_len, 0);
+            let n = self.file.read_at(buffer, range.start)?;
+            buffer.truncate(n);
+
+            Ok((range.end - range.start) as usize)
         }
 
         /// Receive work.
@@ -223,6 +283,32 @@ mod worker {
         }
     }
 
+    /// Wraps the real sink and discards matches that start at or past
+    /// `boundary` (buffer-relative): those only showed up here because of
+    /// the range's overlap and are owned by the next range's own scan.
+    ///
+    /// `SinkMatch` has no public constructor, so there's no way to hand
+    /// `inner` a copy with file-global offsets directly; instead this
+    /// forwards through [`OffsetAwareSink::matched_in_chunk`] with
+    /// `base_offset`, whose blanket default ignores it, so `inner` sees
+    /// buffer-relative offsets.
+    struct BoundarySink<S: Sink> {
+        inner: Arc<Mutex<S>>,
+        boundary: usize,
+        base_offset: u64,
+    }
+
+    impl<S: Sink> Sink for BoundarySink<S> {
+        type Error = S::Error;
+
+        fn matched(&mut self, searcher: &Searcher, mat: &SinkMatch<'_>) -> Result<bool, Self::Error> {
+            if mat.bytes_range_in_buffer().start >= self.boundary {
+                return Ok(true);
+            }
+            self.inner.lock().unwrap().matched_in_chunk(searcher, mat, self.base_offset)
+        }
+    }
+
     pub fn split_into_ranges(number: u64, step: u64) -> Vec<Range<u64>> {
         if number <= step {
             return vec![0..number];